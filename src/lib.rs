@@ -1,4 +1,11 @@
 use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+/// Maximum nesting depth `@file` response-file expansion will follow before
+/// giving up, to guard against files that reference each other in a loop.
+const MAX_RESPONSE_FILE_DEPTH: usize = 10;
 
 /// Structure that holds the arguments passed to executable
 ///
@@ -6,12 +13,18 @@ use std::env;
 /// * `executable`: path of called executable, this is typically the first argument received
 /// * `commands`: list of commands passed to the executable
 /// * `flags`: list of flags passed to the executable
+/// * `options`: list of `(name, value)` pairs passed to the executable
 /// * `paths`: list of paths passed after commands and flags
+/// * `paths_os`: same as `paths`, preserved losslessly; only filled by [`ArgumentConfig::init_os`]
+/// * `spec`: declarative flag/option descriptions registered via [`ArgumentConfig::with_spec`]
+/// * `errors`: response-file read failures from [`ArgumentConfig::init`] and validation
+///   failures collected by [`ArgumentConfig::with_spec`], instead of panicking
+/// * `subcommand`: the scoped subcommand captured by [`ArgumentConfig::init_with_subcommand`]
 ///
 /// # Examples
 /// A executable call can look like this
 ///
-///         ./bin [-fx] [--verbose] <foo> <bar> -- <from> [to]
+///         ./bin [-fx] [--verbose] --name World <foo> <bar> -- <from> [to]
 ///
 /// Which then would be parsed into
 ///
@@ -19,6 +32,7 @@ use std::env;
 ///             executable: "./bin",
 ///             commands: ["foo, "bar"],
 ///             flags: ["f", "x", "verbose"],
+///             options: [("name", "World")],
 ///             paths: ["from", "to"]
 ///         }
 #[derive(Debug)]
@@ -26,9 +40,181 @@ pub struct ArgumentConfig {
     pub executable: String,
     pub commands: Vec<String>,
     pub flags: Vec<String>,
+    pub options: Vec<(String, String)>,
+    pub paths: Vec<String>,
+    pub paths_os: Vec<PathBuf>,
+    pub spec: Vec<ArgumentDescription>,
+    pub errors: Vec<String>,
+    pub subcommand: Option<Subcommand>,
+    /// Which parse_args* variant produced this config, and the raw tokens it
+    /// was given, so [`ArgumentConfig::with_spec`] can reclassify them once a
+    /// spec is available (every constructor parses once with an empty `spec`
+    /// before `with_spec` ever runs).
+    mode: Option<ParseMode>,
+    raw_args: Vec<String>,
+    raw_args_os: Vec<OsString>,
+}
+
+/// Tracks which parse_args* variant [`ArgumentConfig::reparse`] should replay
+#[derive(Debug, Clone, Copy)]
+enum ParseMode {
+    Args,
+    ArgsOs,
+    Subcommand,
+}
+
+/// A subcommand name together with the flags/options/paths scoped to it
+///
+/// Populated by [`ArgumentConfig::init_with_subcommand`] once the first bare
+/// command token is seen: every flag/option/path parsed after it is attached
+/// here instead of pooled into the top-level `ArgumentConfig`. Later bare
+/// command tokens still go into the top-level `commands`.
+#[derive(Debug)]
+pub struct Subcommand {
+    pub name: String,
+    pub flags: Vec<String>,
+    pub options: Vec<(String, String)>,
     pub paths: Vec<String>,
 }
 
+impl Subcommand {
+    fn new(name: String) -> Subcommand {
+        Subcommand {
+            name,
+            flags: vec![],
+            options: vec![],
+            paths: vec![],
+        }
+    }
+}
+
+/// The type a spec'd flag/option value must parse into, checked when
+/// [`ArgumentConfig::with_spec`] validates the parsed arguments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueType {
+    Bool,
+    Int,
+    Float,
+    Str,
+}
+
+/// Describes a single flag or option that [`ArgumentConfig`] should recognize once a
+/// spec has been registered via [`ArgumentConfig::with_spec`].
+///
+/// # Examples
+///
+///         ArgumentDescription::new("name").short("n").takes_value(true).required(true)
+#[derive(Debug, Clone)]
+pub struct ArgumentDescription {
+    pub long: String,
+    pub short: Option<String>,
+    pub takes_value: bool,
+    pub required: bool,
+    pub value_type: ValueType,
+    pub description: Option<String>,
+}
+
+impl ArgumentDescription {
+    /// Creates a new optional, value-less, string-typed flag/option description
+    pub fn new(long: &str) -> ArgumentDescription {
+        ArgumentDescription {
+            long: long.to_string(),
+            short: None,
+            takes_value: false,
+            required: false,
+            value_type: ValueType::Str,
+            description: None,
+        }
+    }
+
+    /// Registers the single-character alias this flag/option can also be passed as
+    pub fn short(mut self, short: &str) -> ArgumentDescription {
+        self.short = Some(short.to_string());
+        self
+    }
+
+    /// Sets the human-readable text shown for this entry in `usage()`
+    pub fn description(mut self, description: &str) -> ArgumentDescription {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Marks whether this entry is an option (carries a value) or a bare flag
+    pub fn takes_value(mut self, takes_value: bool) -> ArgumentDescription {
+        self.takes_value = takes_value;
+        self
+    }
+
+    /// Marks whether `validate` should error when this entry is absent
+    pub fn required(mut self, required: bool) -> ArgumentDescription {
+        self.required = required;
+        self
+    }
+
+    /// Sets the type the carried value must parse into
+    pub fn value_type(mut self, value_type: ValueType) -> ArgumentDescription {
+        self.value_type = value_type;
+        self
+    }
+}
+
+/// Parses a raw flag/option value into a concrete type for [`ArgumentConfig::get`]
+pub trait FetchType: Sized {
+    fn fetch(value: &str) -> Option<Self>;
+}
+
+impl FetchType for bool {
+    fn fetch(value: &str) -> Option<bool> {
+        value.parse().ok()
+    }
+}
+
+impl FetchType for i64 {
+    fn fetch(value: &str) -> Option<i64> {
+        value.parse().ok()
+    }
+}
+
+impl FetchType for f64 {
+    fn fetch(value: &str) -> Option<f64> {
+        value.parse().ok()
+    }
+}
+
+impl FetchType for String {
+    fn fetch(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+}
+
+/// Outcome of classifying a single non-divider token, shared by
+/// [`ArgumentConfig::parse_args`], [`ArgumentConfig::parse_args_os`] and
+/// [`ArgumentConfig::parse_args_subcommand`] so the `--key=value`/`--key
+/// value`/`-k value`/grouped-short-flag rules are implemented exactly once.
+enum ParsedToken {
+    /// A lone boolean flag, or every character of a grouped short flag like
+    /// `-fx` whose last character does not carry a value
+    Flags(Vec<String>),
+    /// A `name`/`value` option pair. `consumed_next` is `true` when `value`
+    /// came from the following token (so the caller must advance its
+    /// iterator past it), `false` for the `--key=value` split form.
+    Option {
+        name: String,
+        value: String,
+        consumed_next: bool,
+    },
+    /// A grouped short flag whose last character is a spec'd option that
+    /// takes a value (e.g. `-fc 3` where `c` takes a value): `flags` stays
+    /// boolean, `name`/`value` always consumes the next token.
+    FlagsAndOption {
+        flags: Vec<String>,
+        name: String,
+        value: String,
+    },
+    /// Anything else — a bare command
+    Command,
+}
+
 impl ArgumentConfig {
     /// Creates a new argument config with empty values
     fn new() -> ArgumentConfig {
@@ -36,12 +222,230 @@ impl ArgumentConfig {
             executable: String::new(),
             commands: vec![],
             flags: vec![],
+            options: vec![],
             paths: vec![],
+            paths_os: vec![],
+            spec: vec![],
+            errors: vec![],
+            subcommand: None,
+            mode: None,
+            raw_args: vec![],
+            raw_args_os: vec![],
+        }
+    }
+
+    /// Registers a declarative spec of known flags/options and validates the
+    /// already-parsed arguments against it.
+    ///
+    /// Unknown flags/options, missing required entries and values that fail to
+    /// parse into their declared [`ValueType`] are collected into `errors`
+    /// instead of panicking.
+    ///
+    /// # Returns
+    ///
+    /// Gives back ownership of self so calls can be chained onto [`ArgumentConfig::init`]
+    pub fn with_spec(mut self, spec: Vec<ArgumentDescription>) -> ArgumentConfig {
+        self.spec = spec;
+        self.reparse();
+        self.validate();
+        self
+    }
+
+    /// Reclassifies the tokens this config was built from now that `spec` is
+    /// populated.
+    ///
+    /// Every constructor parses once with an empty `spec` (it's always empty
+    /// at that point, since [`ArgumentConfig::with_spec`] is the only way to
+    /// register one and necessarily runs afterwards), so spec-dependent
+    /// classification — a grouped short flag's trailing value-taking
+    /// character, or a global flag before the subcommand name is known — can't
+    /// be decided correctly until now. Re-running the original parse_args*
+    /// variant against the stored tokens fixes that up.
+    fn reparse(&mut self) {
+        self.commands.clear();
+        self.flags.clear();
+        self.options.clear();
+        self.paths.clear();
+        self.paths_os.clear();
+        self.subcommand = None;
+
+        match self.mode {
+            None => {}
+            Some(ParseMode::Args) => {
+                let mut args = std::mem::take(&mut self.raw_args);
+                self.parse_args(&mut args);
+                self.raw_args = args;
+            }
+            Some(ParseMode::ArgsOs) => {
+                let mut args = std::mem::take(&mut self.raw_args_os);
+                self.parse_args_os(&mut args);
+                self.raw_args_os = args;
+            }
+            Some(ParseMode::Subcommand) => {
+                let mut args = std::mem::take(&mut self.raw_args);
+                self.parse_args_subcommand(&mut args);
+                self.raw_args = args;
+            }
         }
     }
 
+    /// Validates the collected `flags`/`options` against the registered `spec`,
+    /// appending a message to `errors` for every violation found.
+    fn validate(&mut self) {
+        for flag in &self.flags {
+            let known = self
+                .spec
+                .iter()
+                .any(|d| &d.long == flag || d.short.as_deref() == Some(flag.as_str()));
+
+            if !known {
+                self.errors.push(format!("unknown flag: {}", flag));
+            }
+        }
+
+        for (name, value) in &self.options {
+            let desc = self
+                .spec
+                .iter()
+                .find(|d| &d.long == name || d.short.as_deref() == Some(name.as_str()));
+
+            match desc {
+                None => self.errors.push(format!("unknown option: {}", name)),
+                Some(desc) if !Self::value_matches_type(value, desc.value_type) => {
+                    self.errors.push(format!(
+                        "option '{}' expects a {:?} value, got '{}'",
+                        desc.long, desc.value_type, value
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+
+        for desc in &self.spec {
+            let matched_option = self
+                .options
+                .iter()
+                .any(|(n, _)| n == &desc.long || desc.short.as_deref() == Some(n.as_str()));
+            let matched_flag = self
+                .flags
+                .iter()
+                .any(|f| f == &desc.long || desc.short.as_deref() == Some(f.as_str()));
+
+            // The parser only routes a name into `options` when it captured a
+            // value (`=value` or the following token); a takes_value entry that only shows up in
+            // `flags` was passed without one (e.g. spec `-c` alone)
+            if desc.takes_value && matched_flag && !matched_option {
+                self.errors.push(format!("option '{}' requires a value but none was given", desc.long));
+                continue;
+            }
+
+            if desc.required && !matched_option && !matched_flag {
+                self.errors.push(format!("missing required option: {}", desc.long));
+            }
+        }
+    }
+
+    fn value_matches_type(value: &str, value_type: ValueType) -> bool {
+        match value_type {
+            ValueType::Bool => value.parse::<bool>().is_ok(),
+            ValueType::Int => value.parse::<i64>().is_ok(),
+            ValueType::Float => value.parse::<f64>().is_ok(),
+            ValueType::Str => true,
+        }
+    }
+
+    /// Fetches a flag/option value by its long or short name, parsed into `T`
+    ///
+    /// A present boolean flag with no carried value is treated as `true`. Returns
+    /// `None` when the name is absent or its value fails to parse into `T`.
+    pub fn get<T: FetchType>(&self, name: &str) -> Option<T> {
+        // Resolve `name` through the spec so either alias of a spec'd entry matches
+        let desc = self
+            .spec
+            .iter()
+            .find(|d| d.long == name || d.short.as_deref() == Some(name));
+        let matches = |n: &str| {
+            n == name || desc.map_or(false, |d| d.long == n || d.short.as_deref() == Some(n))
+        };
+
+        if let Some((_, value)) = self.options.iter().find(|(n, _)| matches(n)) {
+            return T::fetch(value);
+        }
+
+        if self.flags.iter().any(|f| matches(f)) {
+            return T::fetch("true");
+        }
+
+        None
+    }
+
+    /// Builds a usage/help message describing this program's accepted arguments
+    ///
+    /// When a spec has been registered (see [`ArgumentConfig::with_spec`]) the
+    /// generated text lists the executable name, every known flag/option with its
+    /// short/long forms and description, and a synopsis line. Without a spec, a
+    /// minimal usage line derived from `executable` is returned.
+    pub fn usage(&self) -> String {
+        let synopsis = format!("Usage: {} [OPTIONS] <commands> -- <paths>\n", self.executable);
+
+        if self.spec.is_empty() {
+            return synopsis;
+        }
+
+        let mut out = synopsis;
+        out.push_str("\nOptions:\n");
+
+        for desc in &self.spec {
+            let forms = match &desc.short {
+                Some(short) => format!("-{}, --{}", short, desc.long),
+                None => format!("--{}", desc.long),
+            };
+
+            let value = if desc.takes_value { " <value>" } else { "" };
+            let required = if desc.required { " (required)" } else { "" };
+            let description = match &desc.description {
+                Some(description) => format!("  {}", description),
+                None => String::new(),
+            };
+
+            out.push_str(&format!("    {}{}{}{}\n", forms, value, required, description));
+        }
+
+        out
+    }
+
+    /// Prints `usage()` and exits the process if `-h`/`--help` was passed
+    ///
+    /// Call this after [`ArgumentConfig::with_spec`] (if any) so the generated
+    /// text reflects the registered flags/options.
+    ///
+    /// # Returns
+    ///
+    /// Gives back ownership of self so calls can keep chaining when help wasn't requested
+    pub fn with_help(self) -> ArgumentConfig {
+        if self.help_requested() {
+            println!("{}", self.usage());
+            std::process::exit(0);
+        }
+
+        self
+    }
+
+    /// Whether `-h`/`--help` was passed, whether it landed as a bare flag or
+    /// (per the `--key value`/`-k value` heuristic) as an option that
+    /// swallowed a following token as its value
+    fn help_requested(&self) -> bool {
+        self.flags.iter().any(|f| f == "h" || f == "help")
+            || self.options.iter().any(|(name, _)| name == "h" || name == "help")
+    }
+
     /// Initializes the config reading the arguments passed to the executable
     ///
+    /// Any token of the form `@path` is expanded in-place into the whitespace
+    /// separated tokens read from `path` before parsing, so arguments can be
+    /// kept in a file instead of passed directly on the command line. Failures
+    /// reading a response file are collected into `errors` rather than panicking.
+    ///
     /// # Returns
     ///
     /// Gives back ownership of an ArgumentConfig instance with all parameters loaded into itself
@@ -58,18 +462,168 @@ impl ArgumentConfig {
         }
 
         let mut this = ArgumentConfig::new();
-        this.parse_args(&mut clean_args);
+        this.raw_args = Self::expand_response_files(clean_args, &mut this.errors, 0);
+        this.mode = Some(ParseMode::Args);
+        this.reparse();
         this
     }
 
-    /// Reads arguments passed from the commandline into itself.
-    fn parse_args(&mut self, args: &mut Vec<String>) {
-        let commands = &mut self.commands;
-        let flags = &mut self.flags;
-        let paths = &mut self.paths;
+    /// Recursively replaces `@path` tokens with the tokens read from `path`
+    ///
+    /// `depth` guards against response files that reference each other in a
+    /// loop; once [`MAX_RESPONSE_FILE_DEPTH`] is exceeded the offending token is
+    /// left untouched and an error is recorded instead of recursing further.
+    fn expand_response_files(args: Vec<String>, errors: &mut Vec<String>, depth: usize) -> Vec<String> {
+        let mut expanded = vec![];
 
+        for arg in args {
+            let path = match arg.strip_prefix('@') {
+                Some(path) if depth < MAX_RESPONSE_FILE_DEPTH => path,
+                Some(_) => {
+                    errors.push(format!(
+                        "response file nesting exceeded {} levels at '{}'",
+                        MAX_RESPONSE_FILE_DEPTH, arg
+                    ));
+                    expanded.push(arg);
+                    continue;
+                }
+                None => {
+                    expanded.push(arg);
+                    continue;
+                }
+            };
+
+            match fs::read_to_string(path) {
+                Ok(contents) => {
+                    let tokens = Self::tokenize_response_file(&contents);
+                    expanded.extend(Self::expand_response_files(tokens, errors, depth + 1));
+                }
+                Err(e) => errors.push(format!("failed to read response file '{}': {}", path, e)),
+            }
+        }
+
+        expanded
+    }
+
+    /// Splits response-file contents into arguments on whitespace, treating a
+    /// double-quoted run (e.g. `-w "superduper"`) as a single token
+    fn tokenize_response_file(contents: &str) -> Vec<String> {
+        let mut tokens = vec![];
+        let mut current = String::new();
+        let mut in_quotes = false;
+
+        for c in contents.chars() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                c if c.is_whitespace() && !in_quotes => {
+                    if !current.is_empty() {
+                        tokens.push(std::mem::take(&mut current));
+                    }
+                }
+                c => current.push(c),
+            }
+        }
+
+        if !current.is_empty() {
+            tokens.push(current);
+        }
+
+        tokens
+    }
+
+    /// Classifies a single token that is neither the `--` divider nor a path
+    /// after it.
+    ///
+    /// `peek` is the next raw token, consulted to decide whether it should be
+    /// consumed as this token's value (it isn't if it looks like another
+    /// flag). `bare_value_allowed` gates that heuristic for a *non-grouped*
+    /// long/short flag when no `=` was used: pass `false` to only let `peek`
+    /// be consumed when `spec` declares the flag `takes_value` (used by
+    /// [`ArgumentConfig::parse_args_subcommand`] before the subcommand name is
+    /// known, so a global flag can't swallow it). A grouped short flag's
+    /// trailing value-taking character (e.g. the `c` in `-fc`) always consults
+    /// `spec`, regardless of `bare_value_allowed`.
+    fn classify_token(
+        token: &str,
+        peek: Option<&str>,
+        spec: &[ArgumentDescription],
+        bare_value_allowed: bool,
+    ) -> ParsedToken {
+        let spec_takes_value = |name: &str| {
+            spec.iter()
+                .any(|d| (d.long == name || d.short.as_deref() == Some(name)) && d.takes_value)
+        };
+
+        let value_from_peek = |name: &str| -> Option<String> {
+            let next = peek?;
+            if next.starts_with('-') {
+                return None;
+            }
+            if bare_value_allowed || spec_takes_value(name) {
+                return Some(next.to_string());
+            }
+            None
+        };
+
+        // Check for large flags/options
+        if let Some(name) = token.strip_prefix("--") {
+            // "--key=value" form, splitting on the first '='
+            if let Some(idx) = name.find('=') {
+                return ParsedToken::Option {
+                    name: name[..idx].to_string(),
+                    value: name[idx + 1..].to_string(),
+                    consumed_next: false,
+                };
+            }
+
+            // "--key value" form: the next token is the value unless it
+            // looks like another flag/option
+            return match value_from_peek(name) {
+                Some(value) => ParsedToken::Option { name: name.to_string(), value, consumed_next: true },
+                None => ParsedToken::Flags(vec![name.to_string()]),
+            };
+        }
+
+        // Check for small flags/options
+        if let Some(rest) = token.strip_prefix('-') {
+            // Grouped flags? eg "-fx"
+            if rest.len() > 1 {
+                let mut chars: Vec<String> = rest.chars().map(String::from).collect();
+                let last = chars.last().unwrap().clone();
+
+                // Only the trailing char in the group can carry a value, and only
+                // if the spec says its short name expects one
+                if spec_takes_value(&last) {
+                    if let Some(next) = peek {
+                        if !next.starts_with('-') {
+                            chars.pop();
+                            return ParsedToken::FlagsAndOption {
+                                flags: chars,
+                                name: last,
+                                value: next.to_string(),
+                            };
+                        }
+                    }
+                }
+
+                return ParsedToken::Flags(chars);
+            }
+
+            // "-t value" form: the next token is the value unless it
+            // looks like another flag/option
+            return match value_from_peek(rest) {
+                Some(value) => ParsedToken::Option { name: rest.to_string(), value, consumed_next: true },
+                None => ParsedToken::Flags(vec![rest.to_string()]),
+            };
+        }
+
+        ParsedToken::Command
+    }
+
+    /// Reads arguments passed from the commandline into itself.
+    fn parse_args(&mut self, args: &mut [String]) {
         let mut path_divider = false;
-        let mut iter = args.iter();
+        let mut iter = args.iter().peekable();
         self.executable = match iter.next() {
             None => String::new(),
             Some(arg) => arg.to_string(),
@@ -88,30 +642,420 @@ impl ArgumentConfig {
 
             // We know that after '--' every arg is a filesystem path
             if path_divider {
-                paths.push(arg.to_string());
+                self.paths.push(arg.to_string());
                 continue;
             }
 
-            // Check for large flags
-            if arg.starts_with("--") {
-                flags.push(String::from(&arg[2..]));
+            let peek = iter.peek().map(|s| s.as_str());
+
+            match Self::classify_token(arg, peek, &self.spec, true) {
+                ParsedToken::Command => self.commands.push(arg.to_string()),
+                ParsedToken::Flags(new_flags) => self.flags.extend(new_flags),
+                ParsedToken::Option { name, value, consumed_next } => {
+                    self.options.push((name, value));
+                    if consumed_next {
+                        iter.next();
+                    }
+                }
+                ParsedToken::FlagsAndOption { flags, name, value } => {
+                    self.flags.extend(flags);
+                    self.options.push((name, value));
+                    iter.next();
+                }
+            }
+        }
+    }
+
+    /// Initializes the config reading the arguments passed to the executable via
+    /// [`std::env::args_os`] instead of [`std::env::args`]
+    ///
+    /// Unlike [`ArgumentConfig::init`], a path passed after `--` that is not valid
+    /// Unicode survives intact into `paths_os` instead of causing a panic. Commands,
+    /// flags and options are still handled lossily since they're matched as `&str`.
+    /// Response-file expansion is not performed in this mode.
+    ///
+    /// # Returns
+    ///
+    /// Gives back ownership of an ArgumentConfig instance with all parameters loaded into itself
+    pub fn init_os() -> ArgumentConfig {
+        let mut this = ArgumentConfig::new();
+        this.raw_args_os = env::args_os().collect();
+        this.mode = Some(ParseMode::ArgsOs);
+        this.reparse();
+        this
+    }
+
+    /// Reads arguments passed from the commandline into itself, preserving `paths_os`
+    /// losslessly even when they are not valid Unicode.
+    fn parse_args_os(&mut self, args: &mut [OsString]) {
+        let mut path_divider = false;
+        let mut iter = args.iter().peekable();
+        self.executable = match iter.next() {
+            None => String::new(),
+            Some(arg) => arg.to_string_lossy().into_owned(),
+        };
+
+        loop {
+            let arg = match iter.next() {
+                None => break,
+                Some(arg) => arg,
+            };
+
+            if arg == "--" {
+                path_divider = true;
+                continue;
+            }
+
+            // We know that after '--' every arg is a filesystem path
+            if path_divider {
+                self.paths.push(arg.to_string_lossy().into_owned());
+                self.paths_os.push(PathBuf::from(arg));
                 continue;
             }
 
-            // Check for small flags
-            if arg.starts_with("-") {
-                // Grouped flags? eg "-fx"
-                if arg.len() > 2 {
-                    arg[1..].chars().for_each(|c| flags.push(String::from(c)));
-                } else {
-                    flags.push(String::from(&arg[1..]));
+            let lossy = arg.to_string_lossy();
+            let peek_lossy = iter.peek().map(|t| t.to_string_lossy());
+            let peek = peek_lossy.as_deref();
+
+            match Self::classify_token(&lossy, peek, &self.spec, true) {
+                ParsedToken::Command => self.commands.push(lossy.into_owned()),
+                ParsedToken::Flags(new_flags) => self.flags.extend(new_flags),
+                ParsedToken::Option { name, value, consumed_next } => {
+                    self.options.push((name, value));
+                    if consumed_next {
+                        iter.next();
+                    }
                 }
+                ParsedToken::FlagsAndOption { flags, name, value } => {
+                    self.flags.extend(flags);
+                    self.options.push((name, value));
+                    iter.next();
+                }
+            }
+        }
+    }
+
+    /// Initializes the config treating the first bare command as a subcommand name
+    ///
+    /// Mirrors [`ArgumentConfig::init`], except once the first non-flag, non-path
+    /// token is seen it becomes `subcommand.name` and every flag/option/path parsed
+    /// after it is scoped to `subcommand` instead of pooled into the top-level
+    /// `flags`/`options`/`paths`. This lets `./bin build --release -- src/` produce
+    /// a `build` subcommand carrying its own `--release` flag and `src/` path.
+    ///
+    /// # Returns
+    ///
+    /// Gives back ownership of an ArgumentConfig instance with all parameters loaded into itself
+    pub fn init_with_subcommand() -> ArgumentConfig {
+        let mut args = env::args();
+        let mut clean_args = vec![];
+
+        loop {
+            let arg = args.next();
+            match arg {
+                None => break,
+                Some(arg) => clean_args.push(arg),
+            }
+        }
+
+        let mut this = ArgumentConfig::new();
+        this.raw_args = clean_args;
+        this.mode = Some(ParseMode::Subcommand);
+        this.reparse();
+        this
+    }
+
+    /// Reads arguments passed from the commandline, scoping everything parsed after
+    /// the first bare command token into `subcommand` rather than the top level.
+    fn parse_args_subcommand(&mut self, args: &mut [String]) {
+        let mut path_divider = false;
+        let mut iter = args.iter().peekable();
+        self.executable = match iter.next() {
+            None => String::new(),
+            Some(arg) => arg.to_string(),
+        };
+
+        loop {
+            let arg = match iter.next() {
+                None => break,
+                Some(arg) => arg,
+            };
 
+            if arg.eq(&"--") {
+                path_divider = true;
                 continue;
             }
 
-            // If neither flags nor paths, then commands
-            commands.push(arg.to_string());
+            // We know that after '--' every arg is a filesystem path
+            if path_divider {
+                match &mut self.subcommand {
+                    Some(sub) => sub.paths.push(arg.to_string()),
+                    None => self.paths.push(arg.to_string()),
+                }
+                continue;
+            }
+
+            let peek = iter.peek().map(|s| s.as_str());
+
+            // Before the subcommand name is known, a global flag must not be
+            // able to consume it as a bare value (e.g. `--verbose build`): only
+            // let peek be eaten here when the spec explicitly says so. Once the
+            // subcommand is set, scoped flags/options use the normal heuristic.
+            let bare_value_allowed = self.subcommand.is_some();
+
+            match Self::classify_token(arg, peek, &self.spec, bare_value_allowed) {
+                // The first bare command becomes the subcommand name; once a
+                // subcommand is active, further bare tokens are just commands
+                // scoped to the global config
+                ParsedToken::Command => match &mut self.subcommand {
+                    Some(_) => self.commands.push(arg.to_string()),
+                    None => self.subcommand = Some(Subcommand::new(arg.to_string())),
+                },
+                ParsedToken::Flags(new_flags) => match &mut self.subcommand {
+                    Some(sub) => sub.flags.extend(new_flags),
+                    None => self.flags.extend(new_flags),
+                },
+                ParsedToken::Option { name, value, consumed_next } => {
+                    match &mut self.subcommand {
+                        Some(sub) => sub.options.push((name, value)),
+                        None => self.options.push((name, value)),
+                    }
+                    if consumed_next {
+                        iter.next();
+                    }
+                }
+                ParsedToken::FlagsAndOption { flags, name, value } => {
+                    match &mut self.subcommand {
+                        Some(sub) => {
+                            sub.flags.extend(flags);
+                            sub.options.push((name, value));
+                        }
+                        None => {
+                            self.flags.extend(flags);
+                            self.options.push((name, value));
+                        }
+                    }
+                    iter.next();
+                }
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_option_empty_value_after_equals_is_not_dropped() {
+        let mut cfg = ArgumentConfig::new();
+        cfg.parse_args(&mut ["bin".to_string(), "--key=".to_string()]);
+        assert_eq!(cfg.options, vec![("key".to_string(), String::new())]);
+    }
+
+    #[test]
+    fn lone_divider_switches_to_path_mode_without_being_a_path() {
+        let mut cfg = ArgumentConfig::new();
+        cfg.parse_args(&mut ["bin".to_string(), "--".to_string(), "foo".to_string()]);
+        assert!(cfg.flags.is_empty());
+        assert!(cfg.options.is_empty());
+        assert_eq!(cfg.paths, vec!["foo".to_string()]);
+    }
+
+    #[test]
+    fn grouped_short_flags_expand_as_booleans_without_a_spec() {
+        let mut cfg = ArgumentConfig::new();
+        cfg.parse_args(&mut ["bin".to_string(), "-fx".to_string()]);
+        assert_eq!(cfg.flags, vec!["f".to_string(), "x".to_string()]);
+    }
+
+    #[test]
+    fn grouped_short_flags_route_trailing_value_taking_char_to_options() {
+        // Mirrors the real call order: a constructor parses once with an
+        // empty spec, and `with_spec` is attached afterwards — it must
+        // reclassify, not just validate, for this to work.
+        let mut cfg = ArgumentConfig::new();
+        cfg.raw_args = vec!["bin".to_string(), "-fc".to_string(), "3".to_string()];
+        cfg.mode = Some(ParseMode::Args);
+        cfg.reparse();
+        let cfg = cfg.with_spec(vec![ArgumentDescription::new("count").short("c").takes_value(true)]);
+
+        assert_eq!(cfg.flags, vec!["f".to_string()]);
+        assert_eq!(cfg.options, vec![("c".to_string(), "3".to_string())]);
+    }
+
+    #[test]
+    fn global_flag_before_subcommand_does_not_swallow_it() {
+        let mut cfg = ArgumentConfig::new();
+        cfg.parse_args_subcommand(&mut [
+            "bin".to_string(),
+            "--verbose".to_string(),
+            "build".to_string(),
+            "--release".to_string(),
+        ]);
+
+        assert_eq!(cfg.flags, vec!["verbose".to_string()]);
+
+        let sub = cfg.subcommand.expect("subcommand should have been captured");
+        assert_eq!(sub.name, "build");
+        assert_eq!(sub.flags, vec!["release".to_string()]);
+    }
+
+    #[test]
+    fn spec_aware_global_option_before_subcommand_still_consumes_its_value() {
+        // Mirrors the real call order: init_with_subcommand() parses once
+        // with an empty spec, and with_spec is attached afterwards.
+        let mut cfg = ArgumentConfig::new();
+        cfg.raw_args = vec![
+            "bin".to_string(),
+            "--name".to_string(),
+            "world".to_string(),
+            "build".to_string(),
+        ];
+        cfg.mode = Some(ParseMode::Subcommand);
+        cfg.reparse();
+        let cfg = cfg.with_spec(vec![ArgumentDescription::new("name").short("n").takes_value(true)]);
+
+        assert_eq!(cfg.options, vec![("name".to_string(), "world".to_string())]);
+        assert_eq!(cfg.subcommand.expect("subcommand should have been captured").name, "build");
+    }
+
+    #[test]
+    fn help_flag_consumed_as_an_option_value_is_still_detected() {
+        let mut cfg = ArgumentConfig::new();
+        cfg.parse_args(&mut ["bin".to_string(), "-h".to_string(), "status".to_string()]);
+
+        assert_eq!(cfg.options, vec![("h".to_string(), "status".to_string())]);
+        assert!(cfg.help_requested());
+    }
+
+    #[test]
+    fn bare_help_flag_is_detected() {
+        let mut cfg = ArgumentConfig::new();
+        cfg.parse_args(&mut ["bin".to_string(), "--help".to_string()]);
+
+        assert!(cfg.help_requested());
+    }
+
+    #[test]
+    fn required_value_taking_option_without_a_value_is_an_error() {
+        let mut cfg = ArgumentConfig::new();
+        cfg.parse_args(&mut ["bin".to_string(), "-c".to_string()]);
+        cfg.spec = vec![ArgumentDescription::new("count")
+            .short("c")
+            .takes_value(true)
+            .required(true)];
+        cfg.validate();
+
+        assert!(cfg.errors.iter().any(|e| e.contains("count") && e.contains("requires a value")));
+        assert!(!cfg.errors.iter().any(|e| e.contains("missing required option")));
+    }
+
+    #[test]
+    fn required_option_with_a_value_passes_validation() {
+        let mut cfg = ArgumentConfig::new();
+        cfg.parse_args(&mut ["bin".to_string(), "-c".to_string(), "3".to_string()]);
+        cfg.spec = vec![ArgumentDescription::new("count")
+            .short("c")
+            .takes_value(true)
+            .required(true)
+            .value_type(ValueType::Int)];
+        cfg.validate();
+
+        assert!(cfg.errors.is_empty());
+    }
+
+    #[test]
+    fn usage_lists_short_alias_required_marker_and_description() {
+        let mut cfg = ArgumentConfig::new();
+        cfg.executable = "bin".to_string();
+        let cfg = cfg.with_spec(vec![ArgumentDescription::new("name")
+            .short("n")
+            .takes_value(true)
+            .required(true)
+            .description("the name to greet")]);
+
+        assert_eq!(
+            cfg.usage(),
+            "Usage: bin [OPTIONS] <commands> -- <paths>\n\nOptions:\n    -n, --name <value> (required)  the name to greet\n"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn invalid_utf8_path_after_divider_round_trips_through_paths_os() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let invalid = OsString::from_vec(vec![0x66, 0x6f, 0xff, 0x6f]); // "fo\xFFo"
+        let mut cfg = ArgumentConfig::new();
+        cfg.parse_args_os(&mut [
+            OsString::from("bin"),
+            OsString::from("--"),
+            invalid.clone(),
+        ]);
+
+        assert_eq!(cfg.paths_os, vec![PathBuf::from(invalid)]);
+    }
+
+    fn temp_file_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("argparser_test_{}_{}.txt", std::process::id(), name))
+    }
+
+    #[test]
+    fn response_file_keeps_a_double_quoted_run_as_one_token() {
+        let path = temp_file_path("quoted");
+        fs::write(&path, "-w \"super duper\" --flag").unwrap();
+
+        let mut errors = vec![];
+        let expanded =
+            ArgumentConfig::expand_response_files(vec![format!("@{}", path.display())], &mut errors, 0);
+
+        fs::remove_file(&path).unwrap();
+
+        assert!(errors.is_empty());
+        assert_eq!(expanded, vec!["-w".to_string(), "super duper".to_string(), "--flag".to_string()]);
+    }
+
+    #[test]
+    fn response_file_recursively_expands_a_nested_response_file() {
+        let inner = temp_file_path("inner");
+        let outer = temp_file_path("outer");
+        fs::write(&inner, "--inner-flag").unwrap();
+        fs::write(&outer, format!("--outer-flag @{}", inner.display())).unwrap();
+
+        let mut errors = vec![];
+        let expanded =
+            ArgumentConfig::expand_response_files(vec![format!("@{}", outer.display())], &mut errors, 0);
+
+        fs::remove_file(&inner).unwrap();
+        fs::remove_file(&outer).unwrap();
+
+        assert!(errors.is_empty());
+        assert_eq!(expanded, vec!["--outer-flag".to_string(), "--inner-flag".to_string()]);
+    }
+
+    #[test]
+    fn response_file_nesting_past_the_depth_limit_is_recorded_as_an_error() {
+        let mut errors = vec![];
+        let expanded = ArgumentConfig::expand_response_files(
+            vec!["@does-not-need-to-exist".to_string()],
+            &mut errors,
+            MAX_RESPONSE_FILE_DEPTH,
+        );
+
+        assert_eq!(expanded, vec!["@does-not-need-to-exist".to_string()]);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("nesting exceeded"));
+    }
+
+    #[test]
+    fn response_file_read_failure_is_recorded_as_an_error_not_panicking() {
+        let mut errors = vec![];
+        let expanded =
+            ArgumentConfig::expand_response_files(vec!["@/no/such/path".to_string()], &mut errors, 0);
+
+        assert!(expanded.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("/no/such/path"));
+    }
+}